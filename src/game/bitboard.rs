@@ -5,6 +5,9 @@
 use std::fmt;
 use std::ops;
 use std::simd::{cmp::SimdPartialEq, num::SimdUint, u64x4};
+use std::sync::OnceLock;
+
+use super::extract_lsb;
 
 /// Mask representing all squares that are not on the A file.
 const NOT_A_FILE: u64 = 0xfefefefefefefefe;
@@ -15,6 +18,43 @@ const NOT_H_FILE: u64 = 0x7f7f7f7f7f7f7f7f;
 /// Mask representing a filled board.
 const FILLED: u64 = u64::MAX;
 
+/// Random keys used to hash a [`Bitboard`] for the
+/// transposition table.
+///
+/// One key per square for each side, plus a single key mixed in to
+/// distinguish a position reached after a pass from the same position
+/// reached directly. Generated once with a fixed seed so hashes (and thus
+/// transposition table contents) are reproducible from run to run.
+struct ZobristKeys {
+    me: [u64; 64],
+    op: [u64; 64],
+    pass: u64,
+}
+
+fn zobrist_keys() -> &'static ZobristKeys {
+    static KEYS: OnceLock<ZobristKeys> = OnceLock::new();
+    KEYS.get_or_init(|| {
+        // splitmix64, fixed-seeded for reproducibility.
+        let mut state: u64 = 0x9e3779b97f4a7c15;
+        let mut next = move || {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^ (z >> 31)
+        };
+
+        let mut me = [0u64; 64];
+        let mut op = [0u64; 64];
+        for (me_key, op_key) in me.iter_mut().zip(op.iter_mut()) {
+            *me_key = next();
+            *op_key = next();
+        }
+
+        ZobristKeys { me, op, pass: next() }
+    })
+}
+
 /// A vectorized Kogge-Stone flood fill
 ///
 /// A standard [Kogge-Stone fill] that computes the fill in 4 directions in the
@@ -69,6 +109,150 @@ fn vectorized_shift<const SHR: bool>(gen: u64x4) -> u64x4 {
     shift(gen, SHIFTS) & masks
 }
 
+/// Base positional weight per square, used by [`Bitboard::evaluate`].
+///
+/// Corners are weighted heavily in the occupying side's favor. The X- and
+/// C-squares next to each corner are left at zero here since their real
+/// value depends on whether the corner itself is still empty -- see
+/// [`corner_adjacency_score`].
+#[rustfmt::skip]
+const SQUARE_WEIGHTS: [i32; 64] = [
+    20,  0,  6,  4,  4,  6,  0, 20,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     6,  0,  4,  2,  2,  4,  0,  6,
+     4,  0,  2,  1,  1,  2,  0,  4,
+     4,  0,  2,  1,  1,  2,  0,  4,
+     6,  0,  4,  2,  2,  4,  0,  6,
+     0,  0,  0,  0,  0,  0,  0,  0,
+    20,  0,  6,  4,  4,  6,  0, 20,
+];
+
+/// `(corner, x_square, c_square_a, c_square_b)` for each of the board's four
+/// corners.
+const CORNER_ADJACENCY: [(usize, usize, usize, usize); 4] = [
+    (0, 9, 1, 8),
+    (7, 14, 6, 15),
+    (56, 49, 48, 57),
+    (63, 54, 55, 62),
+];
+
+/// Penalty for sitting next to a corner that is still empty: occupying the
+/// diagonal X-square or either edge C-square can hand that corner straight
+/// to the opponent, but once the corner itself is taken the squares beside
+/// it are just ordinary disks.
+const X_SQUARE_PENALTY: i32 = 40;
+const C_SQUARE_PENALTY: i32 = 20;
+
+/// Scores the X- and C-squares around corners that are still empty, from
+/// the perspective of `me`.
+fn corner_adjacency_score(me: u64, op: u64, empties: u64) -> i32 {
+    let mut score = 0;
+    for &(corner, x_square, c_a, c_b) in CORNER_ADJACENCY.iter() {
+        if empties & (1 << corner) == 0 {
+            continue;
+        }
+        let bit = |sq: usize| 1u64 << sq;
+        if me & bit(x_square) != 0 {
+            score -= X_SQUARE_PENALTY;
+        } else if op & bit(x_square) != 0 {
+            score += X_SQUARE_PENALTY;
+        }
+        for &c in &[c_a, c_b] {
+            if me & bit(c) != 0 {
+                score -= C_SQUARE_PENALTY;
+            } else if op & bit(c) != 0 {
+                score += C_SQUARE_PENALTY;
+            }
+        }
+    }
+    score
+}
+
+/// A set of board squares, represented as a bitmask with one bit per square.
+///
+/// Supports the usual bitwise set operators for masking and combining
+/// squares, and -- via its [`Iterator`] impl -- zero-allocation iteration
+/// over each square in the set, popping the least-significant set bit on
+/// each `next()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Moves(u64);
+
+impl Moves {
+    /// Wraps a raw move mask, as returned by [`Bitboard::get_moves`].
+    pub fn from_mask(mask: u64) -> Self {
+        Self(mask)
+    }
+
+    /// Returns the underlying mask.
+    pub fn mask(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of squares in the set.
+    pub fn count(self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl Iterator for Moves {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if self.0 == 0 {
+            return None;
+        }
+        let lsb = extract_lsb(self.0);
+        self.0 &= !lsb;
+        Some(lsb.trailing_zeros() as u8)
+    }
+}
+
+impl ops::BitAnd for Moves {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl ops::BitOr for Moves {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl ops::BitXor for Moves {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(self.0 ^ rhs.0)
+    }
+}
+
+impl ops::Not for Moves {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl ops::BitAndAssign for Moves {
+    fn bitand_assign(&mut self, rhs: Self) {
+        self.0 &= rhs.0;
+    }
+}
+
+impl ops::BitOrAssign for Moves {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl ops::BitXorAssign for Moves {
+    fn bitxor_assign(&mut self, rhs: Self) {
+        self.0 ^= rhs.0;
+    }
+}
+
 /// A low-level bitboard implemenation for Othello
 ///
 /// Implements move generation and move making for an Othello board relative to
@@ -82,6 +266,12 @@ pub struct Bitboard {
 }
 
 impl Bitboard {
+    /// Constructs a bitboard directly from its `me`/`op` disk masks, e.g.
+    /// when loading a position from outside the normal move-making loop.
+    pub fn from_parts(me: u64, op: u64) -> Self {
+        Self { me, op }
+    }
+
     /// Returns a mask of empty disks
     pub fn empties(self) -> u64 {
         !(self.me | self.op)
@@ -152,10 +342,96 @@ impl Bitboard {
         }
     }
 
+    /// Returns an allocation-free iterator over the squares of
+    /// [`Bitboard::get_moves`].
+    pub fn move_iter(self) -> Moves {
+        Moves::from_mask(self.get_moves())
+    }
+
+    /// Returns a Zobrist hash of the position, keyed by the set bits of `me`
+    /// and `op`.
+    ///
+    /// `just_passed` should be `true` when this position was reached by
+    /// passing, so that it hashes differently from the same disk layout
+    /// reached by a direct move (the two have different legal continuations).
+    pub fn zobrist_hash(self, just_passed: bool) -> u64 {
+        let keys = zobrist_keys();
+
+        let mut hash = 0;
+        let mut me = self.me;
+        while me != 0 {
+            let lsb = extract_lsb(me);
+            me &= !lsb;
+            hash ^= keys.me[lsb.trailing_zeros() as usize];
+        }
+        let mut op = self.op;
+        while op != 0 {
+            let lsb = extract_lsb(op);
+            op &= !lsb;
+            hash ^= keys.op[lsb.trailing_zeros() as usize];
+        }
+        if just_passed {
+            hash ^= keys.pass;
+        }
+        hash
+    }
+
     pub fn score(self) -> i32 {
         self.me.count_ones().try_into().unwrap_or(0) - self.op.count_ones().try_into().unwrap_or(0)
     }
 
+    /// Returns a phased positional evaluation, for use as a leaf heuristic
+    /// where [`Bitboard::score`] (raw disk count) is too noisy.
+    ///
+    /// Combines mobility, the [`SQUARE_WEIGHTS`] table, corner-adjacency
+    /// penalties, and corner occupancy, blending from mobility-dominated in
+    /// the opening/midgame to disk-dominated near the end of the game using
+    /// the number of empty squares as the phase indicator.
+    pub fn evaluate(self) -> i32 {
+        let empties = self.empties();
+
+        let my_moves = self.get_moves().count_ones() as i32;
+        let opp_moves = self.pass().get_moves().count_ones() as i32;
+        let mobility = my_moves - opp_moves;
+
+        let mut positional = 0;
+        let mut me = self.me;
+        let mut op = self.op;
+        for weight in SQUARE_WEIGHTS {
+            if me & 1 != 0 {
+                positional += weight;
+            } else if op & 1 != 0 {
+                positional -= weight;
+            }
+            me >>= 1;
+            op >>= 1;
+        }
+        positional += corner_adjacency_score(self.me, self.op, empties);
+
+        let corners = CORNER_ADJACENCY.iter().fold(0, |acc, &(corner, ..)| {
+            let bit = 1u64 << corner;
+            if self.me & bit != 0 {
+                acc + 1
+            } else if self.op & bit != 0 {
+                acc - 1
+            } else {
+                acc
+            }
+        });
+
+        // Blend mobility and positional terms, shifting weight from
+        // mobility (strong early, with many empties) to raw disk count
+        // (strong late, as the board fills up).
+        let phase = empties.count_ones().min(60) as i32;
+        let mobility_weight = phase;
+        let material_weight = 60 - phase;
+
+        let early = mobility * 8 + positional + corners * 25;
+        let late = self.score() * 20 + positional;
+
+        (early * mobility_weight + late * material_weight) / 60
+    }
+
     pub fn get_me(self) -> u64 {
         self.me
     }
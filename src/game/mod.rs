@@ -2,8 +2,9 @@ mod bitboard;
 mod state;
 
 use std::fmt;
+use std::str::FromStr;
 
-pub use bitboard::Bitboard;
+pub use bitboard::{Bitboard, Moves};
 pub use state::{GameState, MoveType, Side};
 use thiserror::Error;
 
@@ -13,10 +14,10 @@ pub fn extract_lsb(x: u64) -> u64 {
 }
 
 /// An enum representing an Othello move.
-/// 
+///
 /// `Play(index)` represents a disk placed at the index on the board
 /// `Pass` represents a pass
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Move {
     Play(u8),
     Pass,
@@ -26,6 +27,53 @@ pub enum Move {
 #[error("Invalid move played.")]
 pub struct InvalidMoveError;
 
+/// An error produced when parsing a [`Move`] from algebraic notation fails.
+#[derive(Debug, Error)]
+#[error("invalid move notation: \"{0}\" (expected a square like \"e6\" or \"pass\")")]
+pub struct ParseMoveError(String);
+
+impl FromStr for Move {
+    type Err = ParseMoveError;
+
+    /// Parses algebraic move notation, e.g. `"e6"` for a play or `"pass"`
+    /// for a pass, into a [`Move`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("pass") {
+            return Ok(Move::Pass);
+        }
+
+        let bytes = s.as_bytes();
+        if let [file @ b'a'..=b'h', rank @ b'1'..=b'8'] = *bytes {
+            return Ok(Move::Play((rank - b'1') * 8 + (file - b'a')));
+        }
+
+        Err(ParseMoveError(s.to_owned()))
+    }
+}
+
+impl fmt::Display for Move {
+    /// Formats the move as algebraic notation, the inverse of [`Move::from_str`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Move::Play(idx) => {
+                let file = (b'a' + idx % 8) as char;
+                let rank = (b'1' + idx / 8) as char;
+                write!(f, "{file}{rank}")
+            }
+            Move::Pass => write!(f, "pass"),
+        }
+    }
+}
+
+/// An error produced when parsing a board position string fails.
+#[derive(Debug, Error)]
+pub enum ParsePositionError {
+    #[error("expected a 64-character board string, found {0} characters")]
+    InvalidLength(usize),
+    #[error("invalid character '{0}' in board string (expected 'X', 'O', or '.')")]
+    InvalidChar(char),
+}
+
 
 /// A high-level Othello board representation
 /// 
@@ -35,24 +83,14 @@ pub struct Board {
     game_state: GameState,
 }
 
-fn isolate_lsb(x: u64) -> u64 {
-    let (y, _) = x.overflowing_neg();
-    x & y
-}
-
 impl Board {
     pub fn get_moves(&self) -> Vec<Move> {
-        let mut valid_moves = self.bitboard.get_moves();
-        // Create a vec with enough space for all valid moves, or at least one space for a pass
-        let mut moves = Vec::with_capacity(valid_moves.count_ones().try_into().unwrap_or(0).max(1));
-        // Add all valid moves to the vec
-        while valid_moves > 0 {
-            let move_mask = isolate_lsb(valid_moves);
-            valid_moves &= !move_mask;
-            moves.push(Move::Play(move_mask.trailing_zeros().try_into().unwrap_or(0)));
-        }
+        // Compute the move mask once and collect its squares; the `Vec` is
+        // not pre-sized from the mask's length.
+        let mask = self.bitboard.get_moves();
+        let mut moves: Vec<Move> = Moves::from_mask(mask).map(Move::Play).collect();
         // If the vec is empty, then there were no valid moves, so add a pass.
-        if moves.len() == 0 {
+        if moves.is_empty() {
             moves.push(Move::Pass);
         }
         moves
@@ -86,6 +124,55 @@ impl Board {
     pub fn get_game_state(&self) -> GameState {
         self.game_state
     }
+
+    /// Constructs a board from a 64-character position string (`X`/`O`/`.`
+    /// for black/white/empty, in a1..h8 row-major order) and the side to
+    /// move, allowing positions from transcripts or external tooling to be
+    /// loaded directly rather than replayed move by move.
+    pub fn from_position(position: &str, side: Side) -> Result<Self, ParsePositionError> {
+        let len = position.chars().count();
+        if len != 64 {
+            return Err(ParsePositionError::InvalidLength(len));
+        }
+
+        let mut black = 0u64;
+        let mut white = 0u64;
+        for (idx, c) in position.chars().enumerate() {
+            match c {
+                'X' => black |= 1 << idx,
+                'O' => white |= 1 << idx,
+                '.' => {}
+                other => return Err(ParsePositionError::InvalidChar(other)),
+            }
+        }
+
+        let (me, op) = match side {
+            Side::Black => (black, white),
+            Side::White => (white, black),
+        };
+
+        Ok(Self {
+            bitboard: Bitboard::from_parts(me, op),
+            game_state: GameState::new(side, MoveType::Play),
+        })
+    }
+
+    /// Serializes the board to the position string format read by
+    /// [`Board::from_position`].
+    pub fn to_position_string(&self) -> String {
+        let (white, black) = match self.game_state.get_side() {
+            Side::Black => (self.bitboard.get_op(), self.bitboard.get_me()),
+            Side::White => (self.bitboard.get_me(), self.bitboard.get_op()),
+        };
+
+        (0..64)
+            .map(|idx| match (black >> idx & 1, white >> idx & 1) {
+                (1, 0) => 'X',
+                (0, 1) => 'O',
+                _ => '.',
+            })
+            .collect()
+    }
 }
 
 impl Default for Board {
@@ -134,4 +221,67 @@ impl fmt::Display for Board {
         }
         writeln!(f, "  a b c d e f g h")
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_parses_and_displays_known_squares() {
+        assert_eq!("a1".parse::<Move>().unwrap(), Move::Play(0));
+        assert_eq!("h1".parse::<Move>().unwrap(), Move::Play(7));
+        assert_eq!("a8".parse::<Move>().unwrap(), Move::Play(56));
+        assert_eq!("h8".parse::<Move>().unwrap(), Move::Play(63));
+        assert_eq!("e6".parse::<Move>().unwrap(), Move::Play(44));
+        assert_eq!("pass".parse::<Move>().unwrap(), Move::Pass);
+        assert_eq!("PASS".parse::<Move>().unwrap(), Move::Pass);
+
+        assert_eq!(Move::Play(44).to_string(), "e6");
+        assert_eq!(Move::Pass.to_string(), "pass");
+    }
+
+    #[test]
+    fn move_parse_rejects_invalid_notation() {
+        assert!("i9".parse::<Move>().is_err());
+        assert!("e0".parse::<Move>().is_err());
+        assert!("e".parse::<Move>().is_err());
+        assert!("".parse::<Move>().is_err());
+    }
+
+    #[test]
+    fn move_notation_round_trips() {
+        for idx in 0..64u8 {
+            let m = Move::Play(idx);
+            assert_eq!(m.to_string().parse::<Move>().unwrap(), m);
+        }
+        assert_eq!(Move::Pass.to_string().parse::<Move>().unwrap(), Move::Pass);
+    }
+
+    #[test]
+    fn position_round_trips_through_start_position() {
+        let board = Board::default();
+        let position = board.to_position_string();
+
+        let parsed = Board::from_position(&position, board.get_game_state().get_side()).unwrap();
+        assert_eq!(parsed.to_position_string(), position);
+    }
+
+    #[test]
+    fn position_parse_rejects_wrong_length() {
+        assert!(matches!(
+            Board::from_position("...", Side::Black),
+            Err(ParsePositionError::InvalidLength(3))
+        ));
+    }
+
+    #[test]
+    fn position_parse_rejects_invalid_char() {
+        let mut position = ".".repeat(64);
+        position.replace_range(0..1, "?");
+        assert!(matches!(
+            Board::from_position(&position, Side::Black),
+            Err(ParsePositionError::InvalidChar('?'))
+        ));
+    }
 }
\ No newline at end of file
@@ -41,6 +41,12 @@ pub struct GameState {
 }
 
 impl GameState {
+    /// Constructs a game state directly from its side to move and last move
+    /// type, e.g. when loading a position from outside the normal play loop.
+    pub fn new(side: Side, last: MoveType) -> Self {
+        Self { side, last }
+    }
+
     pub fn play(self) -> Self {
         Self { side: self.side.flip(), last: MoveType::Play }
     }
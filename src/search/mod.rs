@@ -1,69 +1,317 @@
-use crate::game::{extract_lsb, Bitboard, Board, GameState, MoveType, Move};
+use std::time::{Duration, Instant};
 
-pub fn negamax(board: &Board, depth: u8) -> Option<(Move, i32)> {
-    if depth == 0 {
+use crate::game::{Bitboard, Board, GameState, Move, Moves, MoveType};
+
+/// Number of slots in a [`TranspositionTable`].
+///
+/// A power of two so that indexing can eventually be turned into a mask if
+/// this becomes a bottleneck; for now a plain modulo is simplest.
+const TT_SIZE: usize = 1 << 20;
+
+/// Which side of the search window a stored score corresponds to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    /// The stored score is the exact value of the node.
+    Exact,
+    /// The stored score is a lower bound (a beta cutoff occurred).
+    Lower,
+    /// The stored score is an upper bound (no move raised alpha).
+    Upper,
+}
+
+/// An entry in the [`TranspositionTable`].
+#[derive(Clone, Copy)]
+struct TTEntry {
+    key: u64,
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    best_move: u32,
+}
+
+/// Encodes a [`Move`] as a `u32` so it can be cached in a [`TTEntry`].
+fn encode_move(m: Move) -> u32 {
+    match m {
+        Move::Play(idx) => idx as u32,
+        Move::Pass => u32::MAX,
+    }
+}
+
+/// Decodes a `u32` produced by [`encode_move`] back into a [`Move`].
+fn decode_move(m: u32) -> Move {
+    if m == u32::MAX {
+        Move::Pass
+    } else {
+        Move::Play(m as u8)
+    }
+}
+
+/// A fixed-size transposition table, indexed by `hash % size` with
+/// always-replace slots.
+struct TranspositionTable {
+    entries: Vec<Option<TTEntry>>,
+}
+
+impl TranspositionTable {
+    fn new(size: usize) -> Self {
+        Self { entries: vec![None; size] }
+    }
+
+    fn slot(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+
+    fn probe(&self, key: u64) -> Option<TTEntry> {
+        self.entries[self.slot(key)].filter(|entry| entry.key == key)
+    }
+
+    fn store(&mut self, entry: TTEntry) {
+        let slot = self.slot(entry.key);
+        self.entries[slot] = Some(entry);
+    }
+}
+
+/// Empty-square threshold below which the generic search hands off to the
+/// exact endgame solver.
+const ENDGAME_EMPTIES_THRESHOLD: u32 = 14;
+
+/// Exhaustively searches to the end of the game, returning the exact final
+/// disk differential (the side to move's disks minus the opponent's) under
+/// perfect play.
+///
+/// Moves are tried fastest-first: ordered by ascending mobility for the
+/// opponent's reply, since restricting the opponent produces cutoffs earlier
+/// near the leaves than the move ordering used by the generic search.
+///
+/// Returns `None` if `deadline` passes before the node finishes searching,
+/// the same cancellation contract as [`negamax_impl`].
+fn solve_endgame(
+    bitboard: Bitboard,
+    game_state: GameState,
+    mut alpha: i32,
+    beta: i32,
+    deadline: Option<Instant>,
+) -> Option<i32> {
+    if let Some(deadline) = deadline {
+        if Instant::now() >= deadline {
+            return None;
+        }
+    }
+
+    let moves = bitboard.move_iter();
+
+    if moves.count() == 0 {
+        if let MoveType::Pass = game_state.get_last() {
+            return Some(bitboard.score());
+        }
+        return solve_endgame(bitboard.pass(), game_state.pass(), -beta, -alpha, deadline).map(|score| -score);
+    }
+
+    let mut ordered_moves: Vec<(u32, u8)> = moves
+        .map(|square| (bitboard.make_move(1 << square).get_moves().count_ones(), square))
+        .collect();
+    ordered_moves.sort_unstable_by_key(|&(reply_mobility, _)| reply_mobility);
+
+    let mut best_score = i32::MIN;
+    for (_, square) in ordered_moves {
+        let score = -solve_endgame(bitboard.make_move(1 << square), game_state.play(), -beta, -alpha, deadline)?;
+        if score > best_score {
+            best_score = score;
+        }
+        if score >= beta {
+            return Some(best_score);
+        }
+        if score > alpha {
+            alpha = score;
+        }
+    }
+
+    Some(best_score)
+}
+
+/// Solves a position for win (`1`), loss (`-1`), or draw (`0`) only, using a
+/// null window around zero. Much faster than [`solve_endgame`] when only the
+/// outcome of the game is needed rather than the exact disk differential.
+fn solve_wld(bitboard: Bitboard, game_state: GameState, deadline: Option<Instant>) -> Option<i32> {
+    solve_endgame(bitboard, game_state, -1, 1, deadline).map(|score| match score {
+        score if score > 0 => 1,
+        score if score < 0 => -1,
+        _ => 0,
+    })
+}
+
+/// Exactly solves the outcome of the game from `board`, provided few enough
+/// empty squares remain for the endgame solver to reach the end of the game.
+///
+/// Returns `1` if the side to move wins, `-1` if they lose, `0` for a draw,
+/// or `None` if there are too many empty squares left to solve outright.
+pub fn solve_outcome(board: &Board) -> Option<i32> {
+    let bitboard = board.get_bitboard();
+    if bitboard.empties().count_ones() >= ENDGAME_EMPTIES_THRESHOLD {
         return None;
     }
+    solve_wld(bitboard, board.get_game_state(), None)
+}
 
-    fn negamax_impl(
-        bitboard: Bitboard,
-        game_state: GameState,
-        mut alpha: i32,
-        beta: i32,
-        depth: u8,
-    ) -> i32 {
-        if depth == 0 {
-            return bitboard.score();
+/// Core alpha-beta recursion, shared by [`negamax`] and [`negamax_timed`].
+///
+/// Returns `None` if `deadline` passes before the node finishes searching,
+/// so that an in-progress iteration can be abandoned cleanly by its caller.
+fn negamax_impl(
+    bitboard: Bitboard,
+    game_state: GameState,
+    mut alpha: i32,
+    beta: i32,
+    depth: u8,
+    tt: &mut TranspositionTable,
+    deadline: Option<Instant>,
+) -> Option<i32> {
+    if let Some(deadline) = deadline {
+        if Instant::now() >= deadline {
+            return None;
         }
+    }
 
-        let mut valid_moves = bitboard.get_moves();
+    // Close to the end of the game it's cheap to solve exactly rather than
+    // stop at a fixed depth, so hand off to the dedicated endgame solver
+    // regardless of how much depth budget is left. `alpha`/`beta` here are on
+    // `evaluate()`'s scale, not the raw disk differential `solve_endgame`
+    // works in, so they can't be passed through as real cutoffs: search the
+    // endgame with a full window instead.
+    if bitboard.empties().count_ones() < ENDGAME_EMPTIES_THRESHOLD {
+        return solve_endgame(bitboard, game_state, i32::MIN + 1, i32::MAX, deadline);
+    }
 
-        if valid_moves == 0 {
-            if let MoveType::Pass = game_state.get_last() {
-                return bitboard.score();
-            }
-            return -negamax_impl(bitboard.pass(), game_state.pass(), -beta, -alpha, depth - 1);
-        }
-
-        while valid_moves > 0 {
-            let move_mask = extract_lsb(valid_moves);
-            valid_moves &= !move_mask;
-            let score = -negamax_impl(
-                bitboard.make_move(move_mask),
-                game_state.play(),
-                -beta,
-                -alpha,
-                depth - 1,
-            );
-            if score >= beta {
-                return beta;
-            }
-            if score > alpha {
-                alpha = score;
+    if depth == 0 {
+        return Some(bitboard.evaluate());
+    }
+
+    let just_passed = matches!(game_state.get_last(), MoveType::Pass);
+    let key = bitboard.zobrist_hash(just_passed);
+    let alpha_orig = alpha;
+
+    let tt_move = match tt.probe(key) {
+        Some(entry) if entry.depth >= depth => {
+            match entry.bound {
+                Bound::Exact => return Some(entry.score),
+                Bound::Lower if entry.score >= beta => return Some(entry.score),
+                Bound::Upper if entry.score <= alpha => return Some(entry.score),
+                _ => Some(decode_move(entry.best_move)),
             }
         }
+        Some(entry) => Some(decode_move(entry.best_move)),
+        None => None,
+    };
 
-        alpha
+    let mut moves = bitboard.move_iter();
+
+    if moves.count() == 0 {
+        if let MoveType::Pass = game_state.get_last() {
+            return Some(bitboard.score());
+        }
+        return negamax_impl(bitboard.pass(), game_state.pass(), -beta, -alpha, depth - 1, tt, deadline)
+            .map(|score| -score);
     }
 
-    let moves = board.get_moves();
+    // Try the move the transposition table remembers as best first, since
+    // it is likely to still be strong and gives alpha-beta its best shot
+    // at an early cutoff.
+    let ordered_first = match tt_move {
+        Some(Move::Play(square)) if moves.mask() & (1u64 << square) != 0 => {
+            moves &= !Moves::from_mask(1u64 << square);
+            Some(square)
+        }
+        _ => None,
+    };
+
+    let mut best_score = i32::MIN;
+    let mut best_move = 0u32;
+    let mut first_move = true;
+
+    // Principal Variation Search: the first (best-ordered) move is searched
+    // with the full window, since it's expected to be the principal
+    // variation. Every later move is first scouted with a null window; only
+    // if it fails high inside the window (meaning it could beat the best
+    // move found so far) do we pay for a full-window re-search to get its
+    // exact value. This only pays off because `ordered_first` puts the
+    // strongest candidate first.
+    for square in ordered_first.into_iter().chain(moves) {
+        let move_mask = 1u64 << square;
+        let child = bitboard.make_move(move_mask);
+        let child_state = game_state.play();
+
+        let score = if first_move {
+            -negamax_impl(child, child_state, -beta, -alpha, depth - 1, tt, deadline)?
+        } else {
+            let scout = -negamax_impl(child, child_state, -alpha - 1, -alpha, depth - 1, tt, deadline)?;
+            if scout > alpha && scout < beta {
+                -negamax_impl(child, child_state, -beta, -scout, depth - 1, tt, deadline)?
+            } else {
+                scout
+            }
+        };
+        first_move = false;
+
+        if score > best_score {
+            best_score = score;
+            best_move = encode_move(Move::Play(square));
+        }
+        if score >= beta {
+            break;
+        }
+        if score > alpha {
+            alpha = score;
+        }
+    }
+
+    let bound = if best_score <= alpha_orig {
+        Bound::Upper
+    } else if best_score >= beta {
+        Bound::Lower
+    } else {
+        Bound::Exact
+    };
+    tt.store(TTEntry {
+        key,
+        depth,
+        score: best_score,
+        bound,
+        best_move,
+    });
+
+    Some(best_score)
+}
+
+/// Searches the root position to a fixed `depth`, trying `moves` in the
+/// given order. Returns `None` if `deadline` passes before the search of
+/// every root move completes.
+fn negamax_root(
+    board: &Board,
+    depth: u8,
+    moves: &[Move],
+    deadline: Option<Instant>,
+    tt: &mut TranspositionTable,
+) -> Option<(Move, i32)> {
     let bitboard = board.get_bitboard();
     let game_state = board.get_game_state();
-    
+
     let mut best_score = i32::MIN;
-    let mut best_move = *moves.get(0)?;
+    let mut best_move = *moves.first()?;
 
-    for mv in moves {
+    for &mv in moves {
+        // Bound each child by the best root score so far, so that a strong
+        // move searched first lets later root moves be cut off early.
+        let alpha = best_score.max(i32::MIN + 1);
         let score = match mv {
             Move::Play(idx) => {
                 let move_mask = 1 << idx;
-                -negamax_impl(bitboard.make_move(move_mask), game_state.play(), i32::MIN + 1, i32::MAX, depth - 1)
+                -negamax_impl(bitboard.make_move(move_mask), game_state.play(), i32::MIN + 1, -alpha, depth - 1, tt, deadline)?
             },
             Move::Pass => {
                 if let MoveType::Pass = game_state.get_last() {
                     return None;
                 }
-                -negamax_impl(bitboard.pass(), game_state.pass(), i32::MIN + 1, i32::MAX, depth - 1)
+                -negamax_impl(bitboard.pass(), game_state.pass(), i32::MIN + 1, -alpha, depth - 1, tt, deadline)?
             }
         };
         if score > best_score {
@@ -74,3 +322,141 @@ pub fn negamax(board: &Board, depth: u8) -> Option<(Move, i32)> {
 
     Some((best_move, best_score))
 }
+
+pub fn negamax(board: &Board, depth: u8) -> Option<(Move, i32)> {
+    if depth == 0 {
+        return None;
+    }
+
+    let moves = board.get_moves();
+    let mut tt = TranspositionTable::new(TT_SIZE);
+    negamax_root(board, depth, &moves, None, &mut tt)
+}
+
+/// Iteratively deepens [`negamax`] until `max_millis` have elapsed, returning
+/// the best move found by the last fully completed depth.
+///
+/// The principal variation move from each completed iteration is tried first
+/// in the next, which sharply improves alpha-beta pruning once the search
+/// gets deep enough for move ordering to matter.
+pub fn negamax_timed(board: &Board, max_millis: u64) -> Option<(Move, i32)> {
+    let deadline = Instant::now() + Duration::from_millis(max_millis);
+    let mut tt = TranspositionTable::new(TT_SIZE);
+    let mut moves = board.get_moves();
+
+    let mut result = None;
+    let mut depth: u8 = 1;
+
+    while Instant::now() < deadline {
+        match negamax_root(board, depth, &moves, Some(deadline), &mut tt) {
+            Some((best_move, score)) => {
+                result = Some((best_move, score));
+                if let Some(pos) = moves.iter().position(|&m| m == best_move) {
+                    moves.swap(0, pos);
+                }
+                depth = depth.saturating_add(1);
+            }
+            None => break,
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays out the default starting position, always taking the
+    /// lowest-indexed legal move (or passing), until at most `target_empties`
+    /// empty squares remain. Used to reach a small, deterministic endgame
+    /// position to test against.
+    fn play_to_empties(target_empties: u32) -> Board {
+        let mut board = Board::default();
+        while board.get_bitboard().empties().count_ones() > target_empties {
+            let moves = board.get_moves();
+            board.play(moves[0]).unwrap();
+        }
+        board
+    }
+
+    /// A plain exhaustive minimax, independent of `solve_endgame`'s move
+    /// ordering and pruning, used as a brute-force oracle to check the real
+    /// search against.
+    fn brute_force_score(bitboard: Bitboard, game_state: GameState) -> i32 {
+        let moves = bitboard.move_iter();
+
+        if moves.count() == 0 {
+            if let MoveType::Pass = game_state.get_last() {
+                return bitboard.score();
+            }
+            return -brute_force_score(bitboard.pass(), game_state.pass());
+        }
+
+        bitboard
+            .move_iter()
+            .map(|square| -brute_force_score(bitboard.make_move(1u64 << square), game_state.play()))
+            .max()
+            .unwrap()
+    }
+
+    #[test]
+    fn transposition_table_round_trips_entries() {
+        let mut tt = TranspositionTable::new(16);
+        let entry = TTEntry { key: 12345, depth: 4, score: 7, bound: Bound::Exact, best_move: 9 };
+
+        assert!(tt.probe(entry.key).is_none());
+
+        tt.store(entry);
+        let probed = tt.probe(entry.key).expect("entry was just stored");
+        assert_eq!(probed.key, entry.key);
+        assert_eq!(probed.depth, entry.depth);
+        assert_eq!(probed.score, entry.score);
+        assert_eq!(probed.bound, entry.bound);
+        assert_eq!(probed.best_move, entry.best_move);
+    }
+
+    #[test]
+    fn transposition_table_probe_rejects_slot_collision() {
+        let mut tt = TranspositionTable::new(16);
+        tt.store(TTEntry { key: 1, depth: 2, score: 3, bound: Bound::Exact, best_move: 0 });
+
+        // Key 17 lands in the same slot as key 1 (17 % 16 == 1), but is a
+        // different position, so the probe must not return key 1's entry.
+        assert!(tt.probe(17).is_none());
+    }
+
+    #[test]
+    fn solve_endgame_matches_brute_force() {
+        let board = play_to_empties(6);
+        let bitboard = board.get_bitboard();
+        let game_state = board.get_game_state();
+
+        let expected = brute_force_score(bitboard, game_state);
+        let actual = solve_endgame(bitboard, game_state, i32::MIN + 1, i32::MAX, None)
+            .expect("unbounded solve never hits a deadline");
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn negamax_finds_exact_best_move_near_endgame() {
+        // With few enough empties, `negamax_impl` hands off to the endgame
+        // solver on the very first node, so its result should be exact and
+        // checkable against the brute-force oracle regardless of `depth`.
+        let board = play_to_empties(6);
+        let bitboard = board.get_bitboard();
+        let game_state = board.get_game_state();
+
+        let expected = brute_force_score(bitboard, game_state);
+        let (best_move, score) = negamax(&board, 4).expect("position has legal moves");
+
+        assert_eq!(score, expected);
+
+        let achieved = match best_move {
+            Move::Play(idx) => -brute_force_score(bitboard.make_move(1u64 << idx), game_state.play()),
+            Move::Pass => -brute_force_score(bitboard.pass(), game_state.pass()),
+        };
+        assert_eq!(achieved, expected);
+    }
+}